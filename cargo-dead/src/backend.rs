@@ -0,0 +1,135 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::{bail, Context, Result};
+
+/// Which analysis strategy collects the set of crates a package's source
+/// actually references. `Syn` is the default fast path; `Lint` trades speed
+/// for precision by asking rustc itself.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    #[default]
+    Syn,
+    Lint,
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Backend::Syn => write!(f, "syn"),
+            Backend::Lint => write!(f, "lint"),
+        }
+    }
+}
+
+/// Compiles a package with `cargo check --message-format=json` under the
+/// stable `unused_crate_dependencies` lint, then reads the emitted
+/// diagnostics back out to find which of `candidates` rustc itself flagged
+/// as unused. Unlike the `syn` backend, this sees through macro expansion
+/// (so it catches macro-only dependencies like `serde_derive`) and real name
+/// resolution (so a local module that happens to share a dependency's name
+/// doesn't register as "using" it). `cargo_args` narrows which targets get
+/// checked, e.g. `["--lib", "--bins"]` vs `["--tests", "--examples",
+/// "--benches"]`, so callers can still attribute usage per dependency scope.
+///
+/// This used to shell out to `cargo check` under `-Zsave-analysis`, but that
+/// flag was removed from rustc years ago. `unused_crate_dependencies` is the
+/// stable replacement: it's a real rustc lint (no nightly, no
+/// `RUSTC_BOOTSTRAP`) that reports exactly "extern crate `X` is unused in
+/// crate `Y`" for every dependency the compiler can prove was never
+/// referenced.
+///
+/// `cargo check --tests --examples --benches` compiles the lib-as-test
+/// binary *and* every individual test/example/bench file as its own
+/// separate crate, so the lint fires once per compiled crate. A dependency
+/// only reaches true "unused" if every one of those crates reports it
+/// unused — a dep referenced from just one `tests/*.rs` file among several
+/// is still in use. So diagnostics are grouped by the reporting crate
+/// first, and only the intersection of each crate's unused set counts as
+/// overall unused; a plain union (what rustc reports for any *one* crate)
+/// would wrongly flag a dependency that a sibling test file or example
+/// still needs.
+pub fn lint_used_crates(
+    manifest_path: &Path,
+    package_name: &str,
+    cargo_args: &[&str],
+    candidates: &HashSet<String>,
+) -> Result<HashSet<String>> {
+    let target_dir = tempfile::tempdir().context("creating a scratch target dir for the lint backend")?;
+
+    let output = Command::new("cargo")
+        .arg("check")
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .arg("-p")
+        .arg(package_name)
+        .args(cargo_args)
+        .arg("--message-format=json")
+        .arg("--target-dir")
+        .arg(target_dir.path())
+        .env("RUSTFLAGS", "-W unused_crate_dependencies")
+        .stdout(Stdio::piped())
+        .output()
+        .context("running cargo check for the lint backend")?;
+
+    if !output.status.success() {
+        bail!("cargo check failed while running the unused-dependency lint for {package_name}");
+    }
+
+    let manifest = manifest_path.to_string_lossy();
+    let mut unused_by_crate: HashMap<String, HashSet<String>> = HashMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        // Only this package's own crates (not its build script or path
+        // dependencies) take part, keyed by source file since a lib and a bin
+        // can share a crate name; one that reports nothing still gets a set.
+        let target = &message["target"];
+        let Some(src_path) = target["src_path"].as_str() else {
+            continue;
+        };
+        if message["manifest_path"].as_str() != Some(&*manifest) || target["kind"] == serde_json::json!(["custom-build"]) {
+            continue;
+        }
+        let unused = unused_by_crate.entry(src_path.to_string()).or_default();
+        if let Some((dep, _)) = unused_crate_from_message(&message["message"]["message"]) {
+            unused.insert(dep);
+        }
+    }
+
+    let unused = intersect_all(unused_by_crate.into_values());
+
+    Ok(candidates.difference(&unused).cloned().collect())
+}
+
+/// Intersects every set in `sets`, treating "no sets at all" (no crate
+/// reported any unused dependency) as "nothing unused" rather than the
+/// empty intersection's usual "everything".
+fn intersect_all(mut sets: impl Iterator<Item = HashSet<String>>) -> HashSet<String> {
+    let Some(mut acc) = sets.next() else {
+        return HashSet::new();
+    };
+    for set in sets {
+        acc = acc.intersection(&set).cloned().collect();
+    }
+    acc
+}
+
+/// `unused_crate_dependencies` diagnostics read `extern crate \`name\` is
+/// unused in crate \`other\``; pull the dependency name and the reporting
+/// crate out of the two backtick-quoted segments rather than depending on
+/// the exact wording around them.
+fn unused_crate_from_message(message: &serde_json::Value) -> Option<(String, String)> {
+    let text = message.as_str()?;
+    if !text.starts_with("extern crate") {
+        return None;
+    }
+    let mut segments = text.split('`');
+    let dep = segments.nth(1)?.to_string();
+    let reporting_crate = segments.nth(1)?.to_string();
+    Some((dep, reporting_crate))
+}
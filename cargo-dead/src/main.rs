@@ -1,6 +1,13 @@
-use cargo_metadata::{Dependency, DependencyKind, MetadataCommand, Package};
-use std::{collections::HashSet, fs, path::Path};
-use toml_edit::{Document, Item};
+mod backend;
+
+use backend::Backend;
+use cargo_metadata::{Dependency, DependencyKind, MetadataCommand, Package, Target};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use toml_edit::{Document, Item, Value};
 use walkdir::WalkDir;
 use syn::visit::Visit;
 use clap::{Parser, Subcommand, Args};
@@ -26,23 +33,252 @@ struct FilterOptions {
     only_build: bool,
     #[arg(long)]
     only_regular: bool,
+    /// Analysis backend used to collect which crates a package's source
+    /// actually references. `lint` is slower (it shells out to `cargo
+    /// check` under the stable `unused_crate_dependencies` lint) but
+    /// resolves macro expansion and name resolution the way rustc itself
+    /// would. The default `syn` backend only sees a crate root through a
+    /// `use`/`extern crate` item or an attribute macro path (e.g.
+    /// `#[tokio::test]`); a crate referenced solely via a fully-qualified
+    /// path like `anyhow::Result` is invisible to it and may be reported
+    /// (and removed by `fix`) as unused. Prefer `lint` for codebases that
+    /// lean on fully-qualified paths instead of `use`.
+    #[arg(long, value_enum, default_value_t = Backend::Syn)]
+    backend: Backend,
+    /// Output format. `json` emits a structured report instead of the
+    /// human-readable text, and suppresses per-package progress lines.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(serde::Serialize)]
+struct UnusedDependency {
+    name: String,
+    manifest_path: String,
+}
+
+/// One package's findings, the unit `Check`/`Fix` accumulate across a
+/// workspace before deciding the process exit code or emitting `--format
+/// json`.
+#[derive(serde::Serialize)]
+struct PackageReport {
+    package: String,
+    unused_normal: Vec<UnusedDependency>,
+    unused_dev: Vec<UnusedDependency>,
+    unused_build: Vec<UnusedDependency>,
+    /// (normalized real name, was it used, scope it is declared under) for
+    /// every `dep = { workspace = true }` entry this package declares. Not
+    /// part of the per-package report; `run` folds these across all members
+    /// to tell whether a `[workspace.dependencies]` entry is unused
+    /// workspace-wide.
+    #[serde(skip)]
+    workspace_dep_usage: Vec<(String, bool, Scope)>,
 }
 
+impl PackageReport {
+    fn has_unused(&self) -> bool {
+        !self.unused_normal.is_empty() || !self.unused_dev.is_empty() || !self.unused_build.is_empty()
+    }
+}
+
+/// The `--format json` output: every member's findings plus any
+/// `[workspace.dependencies]` entry unused by the workspace as a whole.
+#[derive(serde::Serialize)]
+struct Report {
+    packages: Vec<PackageReport>,
+    unused_workspace_dependencies: Vec<UnusedDependency>,
+}
+
+/// Collects crate roots referenced by Rust source, keeping idents found
+/// under `#[cfg(test)]` (almost always a `#[cfg(test)] mod tests { .. }`)
+/// separate from the rest, since they only satisfy dev-dependencies.
 struct CrateVisitor {
-    used_crates: HashSet<String>,
+    normal: HashSet<String>,
+    test: HashSet<String>,
+    in_test_cfg: bool,
+}
+
+impl CrateVisitor {
+    fn new() -> Self {
+        Self {
+            normal: HashSet::new(),
+            test: HashSet::new(),
+            in_test_cfg: false,
+        }
+    }
+
+    /// All idents this visitor collected, regardless of `#[cfg(test)]`.
+    /// Used when scanning a directory whose contents are entirely
+    /// test/example/bench code, so the distinction doesn't matter.
+    fn into_combined(self) -> HashSet<String> {
+        self.normal.into_iter().chain(self.test).collect()
+    }
+}
+
+/// Returns the attributes of an item, for the handful of item kinds that are
+/// commonly annotated with `#[cfg(test)]`.
+fn item_attrs(item: &syn::Item) -> Option<&[syn::Attribute]> {
+    match item {
+        syn::Item::Mod(i) => Some(&i.attrs),
+        syn::Item::Fn(i) => Some(&i.attrs),
+        syn::Item::Use(i) => Some(&i.attrs),
+        syn::Item::ExternCrate(i) => Some(&i.attrs),
+        _ => None,
+    }
+}
+
+/// `crate`, `self`, and `super` are path qualifiers, never crate names, so
+/// they're never worth recording. `std`/`core`/`alloc` are deliberately
+/// *not* filtered here: a project could (rarely) declare a dependency by
+/// one of those names, and letting them through just means they're
+/// harmlessly ignored by the usage check for everyone else.
+fn is_reserved_root(ident: &str) -> bool {
+    matches!(ident, "crate" | "self" | "super")
+}
+
+/// Records the real crate root of every leaf in a `use` tree, following
+/// intermediate path segments back to the root rather than the segment
+/// immediately before the leaf, and following `as` renames back to the name
+/// actually being imported (the rename is only a local alias).
+fn collect_use_tree(tree: &syn::UseTree, root: Option<&str>, used: &mut HashSet<String>) {
+    match tree {
+        syn::UseTree::Path(path) => match root {
+            Some(root) => collect_use_tree(&path.tree, Some(root), used),
+            None => collect_use_tree(&path.tree, Some(&path.ident.to_string()), used),
+        },
+        syn::UseTree::Name(name) => {
+            insert_root(used, root.map(str::to_string).unwrap_or_else(|| name.ident.to_string()));
+        }
+        syn::UseTree::Rename(rename) => {
+            insert_root(used, root.map(str::to_string).unwrap_or_else(|| rename.ident.to_string()));
+        }
+        syn::UseTree::Glob(_) => {
+            if let Some(root) = root {
+                insert_root(used, root.to_string());
+            }
+        }
+        syn::UseTree::Group(group) => {
+            for item in &group.items {
+                collect_use_tree(item, root, used);
+            }
+        }
+    }
+}
+
+fn insert_root(used: &mut HashSet<String>, ident: String) {
+    if !is_reserved_root(&ident) {
+        used.insert(ident);
+    }
+}
+
+fn is_test_cfg(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if !attr.path().is_ident("cfg") {
+            return false;
+        }
+        let mut found = false;
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("test") {
+                found = true;
+            }
+            Ok(())
+        });
+        found
+    })
 }
 
 impl<'ast> Visit<'ast> for CrateVisitor {
-    fn visit_path(&mut self, path: &'ast syn::Path) {
-        if let Some(first_segment) = path.segments.first() {
-            self.used_crates.insert(first_segment.ident.to_string());
+    fn visit_item(&mut self, item: &'ast syn::Item) {
+        let was_test_cfg = self.in_test_cfg;
+        if let Some(attrs) = item_attrs(item) {
+            if is_test_cfg(attrs) {
+                self.in_test_cfg = true;
+            }
+        }
+        syn::visit::visit_item(self, item);
+        self.in_test_cfg = was_test_cfg;
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast syn::Attribute) {
+        // Attribute macros like `#[tokio::test]` or `#[actix_web::main]`
+        // reference a crate directly, with no accompanying `use` — the only
+        // case where recording a bare path root (rather than going through
+        // `visit_item_use`/`visit_item_extern_crate`) is warranted. Ordinary
+        // expression/type paths are deliberately *not* visited this way
+        // anymore: blindly recording every path's first segment treated
+        // local modules that happened to share a dependency's name as
+        // "using" it, hiding genuinely unused dependencies.
+        if let Some(first_segment) = attr.path().segments.first() {
+            let ident = first_segment.ident.to_string();
+            if !is_reserved_root(&ident) {
+                if self.in_test_cfg {
+                    self.test.insert(ident);
+                } else {
+                    self.normal.insert(ident);
+                }
+            }
         }
-        syn::visit::visit_path(self, path);
+        syn::visit::visit_attribute(self, attr);
+    }
+
+    fn visit_item_use(&mut self, item: &'ast syn::ItemUse) {
+        let target = if self.in_test_cfg { &mut self.test } else { &mut self.normal };
+        collect_use_tree(&item.tree, None, target);
+        syn::visit::visit_item_use(self, item);
+    }
+
+    fn visit_item_extern_crate(&mut self, item: &'ast syn::ItemExternCrate) {
+        let ident = item.ident.to_string();
+        if !is_reserved_root(&ident) {
+            if self.in_test_cfg {
+                self.test.insert(ident);
+            } else {
+                self.normal.insert(ident);
+            }
+        }
+        syn::visit::visit_item_extern_crate(self, item);
+    }
+}
+
+/// Which declared-dependency kind a target's usage satisfies.
+#[derive(Clone, Copy)]
+enum Scope {
+    Normal,
+    Dev,
+    Build,
+}
+
+/// Maps a `cargo_metadata` target kind to the dependency scope it satisfies,
+/// mirroring how cargo itself attributes usage to compilation units. `None`
+/// for target kinds that aren't compiled with this package's dependencies
+/// (there are none today, but `kind` is an open-ended list).
+fn target_scope(target: &Target) -> Option<Scope> {
+    if target.kind.iter().any(|k| k == "custom-build") {
+        Some(Scope::Build)
+    } else if target
+        .kind
+        .iter()
+        .any(|k| k == "test" || k == "example" || k == "bench")
+    {
+        Some(Scope::Dev)
+    } else if target.kind.iter().any(|k| {
+        k == "lib" || k == "bin" || k == "proc-macro" || k == "rlib" || k == "cdylib" || k == "staticlib" || k == "dylib"
+    }) {
+        Some(Scope::Normal)
+    } else {
+        None
     }
 }
 
-fn scan_rust_files(dir: &Path) -> anyhow::Result<HashSet<String>> {
-    let mut visitor = CrateVisitor { used_crates: HashSet::new() };
+fn scan_rust_files(dir: &Path) -> anyhow::Result<CrateVisitor> {
+    let mut visitor = CrateVisitor::new();
     for entry in WalkDir::new(dir).into_iter().filter_map(Result::ok) {
         let path = entry.path();
         if path.extension().map_or(false, |ext| ext == "rs") {
@@ -52,55 +288,258 @@ fn scan_rust_files(dir: &Path) -> anyhow::Result<HashSet<String>> {
             }
         }
     }
-    Ok(visitor.used_crates)
+    Ok(visitor)
+}
+
+/// Scans a single Rust source file rather than its whole containing
+/// directory. `build.rs` is the one target whose containing directory is
+/// the package root itself — the same directory that also holds `src/`,
+/// `tests/`, `examples/`, and `benches/` — so walking it the way
+/// `scan_rust_files` does for multi-file targets would absorb every crate
+/// referenced anywhere in the package, not just in the build script.
+fn scan_rust_file(path: &Path) -> anyhow::Result<CrateVisitor> {
+    let mut visitor = CrateVisitor::new();
+    let content = fs::read_to_string(path)?;
+    if let Ok(syntax) = syn::parse_file(&content) {
+        visitor.visit_file(&syntax);
+    }
+    Ok(visitor)
 }
 
 fn get_dependency_names(dependencies: &[Dependency], kind: DependencyKind) -> HashSet<String> {
     dependencies.iter().filter(|dep| dep.kind == kind).map(|dep| dep.name.clone()).collect()
 }
 
-fn analyze_package(package: &Package, fix: bool, filter: &FilterOptions) -> anyhow::Result<()> {
-    println!("\nAnalyzing package: {}", package.name);
+/// Replaces `-` with `_` so a crate name from `Cargo.toml` matches the
+/// identifier it is addressed by in Rust source.
+fn normalize(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+/// Reads `ignored = [...]` out of a `[package.metadata.cargo-dead]` or
+/// `[workspace.metadata.cargo-dead]` table, given the surrounding
+/// `metadata` item (absent when the manifest declares no `[*.metadata]`
+/// table at all, the common case). Missing tables/keys just yield an empty
+/// set rather than panicking.
+fn read_ignored_deps(metadata: Option<&Item>) -> HashSet<String> {
+    metadata
+        .and_then(|metadata| metadata.get("cargo-dead"))
+        .and_then(|cargo_dead| cargo_dead.get("ignored"))
+        .and_then(Item::as_array)
+        .map(|arr| arr.iter().filter_map(Value::as_str).map(normalize).collect())
+        .unwrap_or_default()
+}
+
+/// How a declared dependency is actually referenced in code: the manifest
+/// table key (needed to remove the right entry in `Fix`) and the normalized
+/// identifier derived from that key (needed for the usage check). A
+/// `package = "..."` rename makes both differ from the crate's real name.
+/// `workspace` marks a `dep = { workspace = true }` entry, whose real
+/// source of truth is the root manifest's `[workspace.dependencies]` table.
+struct DepIdent {
+    manifest_key: String,
+    ident: String,
+    workspace: bool,
+}
+
+/// Reads one dependency table item (e.g. `doc.get("dependencies")` or
+/// `workspace_doc["workspace"].get("dependencies")`) and maps each declared
+/// dependency's normalized real name to how it's actually spelled in code,
+/// honoring `package = "..."` renames. `table` is `None`/not a table for a
+/// manifest that declares no such section at all (e.g. no
+/// `[dev-dependencies]`), which is treated the same as an empty one.
+fn dependency_idents(table: Option<&Item>) -> HashMap<String, DepIdent> {
+    let mut idents = HashMap::new();
+    let Some(Item::Table(table)) = table else {
+        return idents;
+    };
+    for (key, item) in table.iter() {
+        let real_name = item.get("package").and_then(Item::as_str).unwrap_or(key);
+        idents.insert(
+            normalize(real_name),
+            DepIdent {
+                manifest_key: key.to_string(),
+                ident: normalize(key),
+                workspace: item.get("workspace").and_then(Item::as_bool).unwrap_or(false),
+            },
+        );
+    }
+    idents
+}
+
+/// Looks up how a declared dependency (named as `cargo_metadata` reports it)
+/// is referenced in code, falling back to its own normalized name if it
+/// isn't present in `idents` (which shouldn't happen, but keeps this total).
+fn resolve_ident<'a>(idents: &'a HashMap<String, DepIdent>, dep: &'a str) -> (&'a str, String, bool) {
+    match idents.get(&normalize(dep)) {
+        Some(ident) => (ident.manifest_key.as_str(), ident.ident.clone(), ident.workspace),
+        None => (dep, normalize(dep), false),
+    }
+}
+
+/// Which dependency kinds `--only-dev`/`--only-build`/`--only-regular`
+/// select for this run; with none given, all three are checked. Shared
+/// between `analyze_package` (to decide what to scan and report) and `run`
+/// (to decide which `[workspace.dependencies]` entries it's even safe to
+/// judge this run).
+fn check_flags(filter: &FilterOptions) -> (bool, bool, bool) {
+    let check_normal = filter.only_regular || (!filter.only_dev && !filter.only_build);
+    let check_dev = filter.only_dev || (!filter.only_regular && !filter.only_build);
+    let check_build = filter.only_build || (!filter.only_regular && !filter.only_dev);
+    (check_normal, check_dev, check_build)
+}
+
+fn analyze_package(
+    package: &Package,
+    fix: bool,
+    filter: &FilterOptions,
+    workspace_ignored: &HashSet<String>,
+) -> anyhow::Result<PackageReport> {
+    let text = filter.format == OutputFormat::Text;
+    if text {
+        println!("\nAnalyzing package: {}", package.name);
+    }
 
     let declared_normal = get_dependency_names(&package.dependencies, DependencyKind::Normal);
     let declared_dev = get_dependency_names(&package.dependencies, DependencyKind::Development);
     let declared_build = get_dependency_names(&package.dependencies, DependencyKind::Build);
 
     let package_root = package.manifest_path.parent().unwrap().as_std_path();
-    let mut used_crates = HashSet::new();
 
-    for dir in ["src", "tests"] {
-        let dir_path = package_root.join(dir);
-        if dir_path.exists() {
-            used_crates.extend(scan_rust_files(&dir_path)?);
+    let cargo_toml_path = package_root.join("Cargo.toml");
+    let mut doc: Document = fs::read_to_string(&cargo_toml_path)?.parse()?;
+    let mut changed = false;
+
+    let normal_idents = dependency_idents(doc.get("dependencies"));
+    let dev_idents = dependency_idents(doc.get("dev-dependencies"));
+    let build_idents = dependency_idents(doc.get("build-dependencies"));
+
+    // Deps ignored via `[package.metadata.cargo-dead]` or the
+    // workspace-level equivalent are a supported escape hatch for crates
+    // only reachable through proc-macro expansion, `#[link]`, or a feature
+    // this scan doesn't enable — skip them entirely rather than report them.
+    let mut ignored = read_ignored_deps(doc.get("package").and_then(|pkg| pkg.get("metadata")));
+    ignored.extend(workspace_ignored.iter().cloned());
+
+    // Usage is attributed per target kind, driven by `package.targets`
+    // rather than a hardcoded `src`/`tests` guess: lib/bin targets satisfy
+    // normal dependencies (their own `#[cfg(test)]` code satisfies
+    // dev-dependencies instead), test/example/bench targets satisfy
+    // dev-dependencies, and the custom-build target satisfies
+    // build-dependencies. This also covers non-standard layouts, since
+    // `src_path` is wherever the target's manifest entry points it.
+    let mut used_normal = HashSet::new();
+    let mut used_dev = HashSet::new();
+    let mut used_build = HashSet::new();
+
+    match filter.backend {
+        Backend::Syn => {
+            let mut scanned_dirs: HashSet<PathBuf> = HashSet::new();
+            for target in &package.targets {
+                let Some(scope) = target_scope(target) else {
+                    continue;
+                };
+                let src_path = target.src_path.as_std_path();
+
+                if let Scope::Build = scope {
+                    // `build.rs` is a single file, not a directory to walk
+                    // (its containing directory is usually the package
+                    // root, shared with every other target).
+                    used_build.extend(scan_rust_file(src_path)?.into_combined());
+                    continue;
+                }
+
+                let dir = src_path.parent().unwrap_or(package_root);
+                if !scanned_dirs.insert(dir.to_path_buf()) {
+                    continue;
+                }
+
+                let visitor = scan_rust_files(dir)?;
+                match scope {
+                    Scope::Normal => {
+                        used_dev.extend(visitor.test);
+                        used_normal.extend(visitor.normal);
+                    }
+                    Scope::Dev => used_dev.extend(visitor.into_combined()),
+                    Scope::Build => unreachable!("handled above"),
+                }
+            }
         }
-    }
+        Backend::Lint => {
+            // The build script stays on the `syn` backend: it's a single,
+            // rarely macro-heavy file, and isolating it from `cargo check`
+            // isn't worth the extra invocation. It's scanned as a single
+            // file, not a directory walk: its containing directory is
+            // usually the package root, shared with `src/`, `tests/`,
+            // `examples/`, and `benches/`.
+            for target in &package.targets {
+                if let Some(Scope::Build) = target_scope(target) {
+                    used_build.extend(scan_rust_file(target.src_path.as_std_path())?.into_combined());
+                }
+            }
+
+            let normal_candidates: HashSet<String> =
+                normal_idents.values().map(|ident| ident.ident.clone()).collect();
+            let dev_candidates: HashSet<String> =
+                dev_idents.values().map(|ident| ident.ident.clone()).collect();
 
-    let build_rs = package_root.join("build.rs");
-    if build_rs.exists() {
-        let content = fs::read_to_string(&build_rs)?;
-        if let Ok(syntax) = syn::parse_file(&content) {
-            let mut visitor = CrateVisitor { used_crates: HashSet::new() };
-            visitor.visit_file(&syntax);
-            used_crates.extend(visitor.used_crates);
+            let manifest_path = package.manifest_path.as_std_path();
+            used_normal = backend::lint_used_crates(
+                manifest_path,
+                &package.name,
+                &["--lib", "--bins"],
+                &normal_candidates,
+            )?;
+            used_dev = backend::lint_used_crates(
+                manifest_path,
+                &package.name,
+                &["--tests", "--examples", "--benches"],
+                &dev_candidates,
+            )?;
         }
     }
 
-    let cargo_toml_path = package_root.join("Cargo.toml");
-    let mut doc: Document = fs::read_to_string(&cargo_toml_path)?.parse()?;
-    let mut changed = false;
+    let (check_normal, check_dev, check_build) = check_flags(filter);
 
-    let check_normal = filter.only_regular || (!filter.only_dev && !filter.only_build);
-    let check_dev = filter.only_dev || (!filter.only_regular && !filter.only_build);
-    let check_build = filter.only_build || (!filter.only_regular && !filter.only_dev);
+    let mut unused_normal = Vec::new();
+    let mut unused_dev = Vec::new();
+    let mut unused_build = Vec::new();
+    // Tracks, for every `dep = { workspace = true }` entry this package
+    // declares, whether it should count as "used" when `run` decides if a
+    // `[workspace.dependencies]` entry is unused by every member, together
+    // with the scope it is declared under (so `run` can avoid pruning a
+    // dependency whose scope wasn't even checked this run, e.g.
+    // `--only-dev`).
+    let mut workspace_dep_usage: Vec<(String, bool, Scope)> = Vec::new();
 
     if check_normal {
         for dep in &declared_normal {
-            if !used_crates.contains(dep) {
-                println!("Unused dependency: {}", dep);
+            let (manifest_key, ident, is_workspace) = resolve_ident(&normal_idents, dep);
+            if ignored.contains(&normalize(dep)) {
+                // An explicitly ignored dependency (e.g. proc-macro-only,
+                // reachable only via `#[link]`) must never make a
+                // `[workspace.dependencies]` entry look workspace-wide
+                // unused just because this member skips reporting on it.
+                if is_workspace {
+                    workspace_dep_usage.push((normalize(dep), true, Scope::Normal));
+                }
+                continue;
+            }
+            let used = used_normal.contains(&ident);
+            if is_workspace {
+                workspace_dep_usage.push((normalize(dep), used, Scope::Normal));
+            }
+            if !used {
+                if text {
+                    println!("Unused dependency: {}", dep);
+                }
+                unused_normal.push(UnusedDependency {
+                    name: dep.clone(),
+                    manifest_path: cargo_toml_path.display().to_string(),
+                });
                 if fix {
                     if let Item::Table(ref mut tbl) = doc["dependencies"] {
-                        tbl.remove(dep);
+                        tbl.remove(manifest_key);
                         changed = true;
                     }
                 }
@@ -110,11 +549,28 @@ fn analyze_package(package: &Package, fix: bool, filter: &FilterOptions) -> anyh
 
     if check_dev {
         for dep in &declared_dev {
-            if !used_crates.contains(dep) {
-                println!("Unused dev-dependency: {}", dep);
+            let (manifest_key, ident, is_workspace) = resolve_ident(&dev_idents, dep);
+            if ignored.contains(&normalize(dep)) {
+                if is_workspace {
+                    workspace_dep_usage.push((normalize(dep), true, Scope::Dev));
+                }
+                continue;
+            }
+            let used = used_dev.contains(&ident);
+            if is_workspace {
+                workspace_dep_usage.push((normalize(dep), used, Scope::Dev));
+            }
+            if !used {
+                if text {
+                    println!("Unused dev-dependency: {}", dep);
+                }
+                unused_dev.push(UnusedDependency {
+                    name: dep.clone(),
+                    manifest_path: cargo_toml_path.display().to_string(),
+                });
                 if fix {
                     if let Item::Table(ref mut tbl) = doc["dev-dependencies"] {
-                        tbl.remove(dep);
+                        tbl.remove(manifest_key);
                         changed = true;
                     }
                 }
@@ -124,11 +580,28 @@ fn analyze_package(package: &Package, fix: bool, filter: &FilterOptions) -> anyh
 
     if check_build {
         for dep in &declared_build {
-            if !used_crates.contains(dep) {
-                println!("Unused build-dependency: {}", dep);
+            let (manifest_key, ident, is_workspace) = resolve_ident(&build_idents, dep);
+            if ignored.contains(&normalize(dep)) {
+                if is_workspace {
+                    workspace_dep_usage.push((normalize(dep), true, Scope::Build));
+                }
+                continue;
+            }
+            let used = used_build.contains(&ident);
+            if is_workspace {
+                workspace_dep_usage.push((normalize(dep), used, Scope::Build));
+            }
+            if !used {
+                if text {
+                    println!("Unused build-dependency: {}", dep);
+                }
+                unused_build.push(UnusedDependency {
+                    name: dep.clone(),
+                    manifest_path: cargo_toml_path.display().to_string(),
+                });
                 if fix {
                     if let Item::Table(ref mut tbl) = doc["build-dependencies"] {
-                        tbl.remove(dep);
+                        tbl.remove(manifest_key);
                         changed = true;
                     }
                 }
@@ -136,34 +609,338 @@ fn analyze_package(package: &Package, fix: bool, filter: &FilterOptions) -> anyh
         }
     }
 
+    // A workspace-inherited dependency in a scope this run didn't check is
+    // still recorded, so `run` leaves its workspace entry untouched.
+    for (checked, idents, scope) in [
+        (check_normal, &normal_idents, Scope::Normal),
+        (check_dev, &dev_idents, Scope::Dev),
+        (check_build, &build_idents, Scope::Build),
+    ] {
+        if checked {
+            continue;
+        }
+        for (name, ident) in idents {
+            if ident.workspace {
+                workspace_dep_usage.push((name.clone(), false, scope));
+            }
+        }
+    }
+
     if fix && changed {
         fs::write(&cargo_toml_path, doc.to_string())?;
-        println!("Updated {}", cargo_toml_path.display());
+        if text {
+            println!("Updated {}", cargo_toml_path.display());
+        }
+    }
+
+    Ok(PackageReport {
+        package: package.name.clone(),
+        unused_normal,
+        unused_dev,
+        unused_build,
+        workspace_dep_usage,
+    })
+}
+
+/// Folds every member's `workspace_dep_usage` records into, for each
+/// `[workspace.dependencies]` entry: whether any member actually used it,
+/// and whether every scope it was seen under was checked this run (so a
+/// `--only-dev` run doesn't treat a normal-only dependency's untouched
+/// `false` as "confirmed unused").
+fn fold_workspace_usage<'a>(
+    workspace_idents: &HashMap<String, DepIdent>,
+    records: impl IntoIterator<Item = &'a (String, bool, Scope)>,
+    check_normal: bool,
+    check_dev: bool,
+    check_build: bool,
+) -> (HashMap<String, bool>, HashMap<String, bool>) {
+    let mut used: HashMap<String, bool> = workspace_idents.keys().map(|name| (name.clone(), false)).collect();
+    let mut eligible: HashMap<String, bool> = workspace_idents.keys().map(|name| (name.clone(), true)).collect();
+    for (name, was_used, scope) in records {
+        let scope_checked = match scope {
+            Scope::Normal => check_normal,
+            Scope::Dev => check_dev,
+            Scope::Build => check_build,
+        };
+        if !scope_checked {
+            if let Some(flag) = eligible.get_mut(name) {
+                *flag = false;
+            }
+            continue;
+        }
+        if let Some(flag) = used.get_mut(name) {
+            *flag |= *was_used;
+        }
     }
+    (used, eligible)
+}
 
-    Ok(())
+fn main() -> std::process::ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            std::process::ExitCode::FAILURE
+        }
+    }
 }
 
-fn main() -> anyhow::Result<()> {
+/// Runs the requested command and decides the process exit code. `Check`
+/// fails CI (non-zero exit) when any unused dependency is found anywhere in
+/// the workspace; findings are accumulated across every member first so a
+/// multi-crate workspace reports everything in one pass instead of exiting
+/// on the first offender.
+fn run() -> anyhow::Result<std::process::ExitCode> {
     let cli = Cli::parse();
     let metadata = MetadataCommand::new().exec()?;
 
-    match cli.command {
-        Commands::Check(ref filter) => {
-            for package in &metadata.packages {
-                if metadata.workspace_members.contains(&package.id) {
-                    analyze_package(package, false, filter)?;
-                }
-            }
+    let workspace_manifest = metadata.workspace_root.as_std_path().join("Cargo.toml");
+    let mut workspace_doc: Document = fs::read_to_string(&workspace_manifest)?.parse()?;
+    let workspace_ignored =
+        read_ignored_deps(workspace_doc.get("workspace").and_then(|ws| ws.get("metadata")));
+
+    let (filter, fix) = match &cli.command {
+        Commands::Check(filter) => (filter, false),
+        Commands::Fix(filter) => (filter, true),
+    };
+    let text = filter.format == OutputFormat::Text;
+
+    let mut reports = Vec::new();
+    for package in &metadata.packages {
+        if metadata.workspace_members.contains(&package.id) {
+            reports.push(analyze_package(package, fix, filter, &workspace_ignored)?);
         }
-        Commands::Fix(ref filter) => {
-            for package in &metadata.packages {
-                if metadata.workspace_members.contains(&package.id) {
-                    analyze_package(package, true, filter)?;
-                }
+    }
+
+    // A `[workspace.dependencies]` entry is unused only if no member that
+    // inherits it (`dep = { workspace = true }`) actually references it in
+    // code — so fold every member's usage before judging the root table.
+    // `--only-dev`/`--only-build`/`--only-regular` mean some members only
+    // ever scanned one scope this run, so an entry seen under a scope that
+    // wasn't checked is left untouched rather than pruned on incomplete
+    // information.
+    let (check_normal, check_dev, check_build) = check_flags(filter);
+    let workspace_idents =
+        dependency_idents(workspace_doc.get("workspace").and_then(|ws| ws.get("dependencies")));
+    let records = reports.iter().flat_map(|report| report.workspace_dep_usage.iter());
+    let (workspace_used, workspace_eligible) =
+        fold_workspace_usage(&workspace_idents, records, check_normal, check_dev, check_build);
+
+    let mut unused_workspace_dependencies = Vec::new();
+    let mut names: Vec<&String> = workspace_used.keys().collect();
+    names.sort();
+    for name in names {
+        if workspace_used[name] || !workspace_eligible[name] || workspace_ignored.contains(name) {
+            continue;
+        }
+        let ident = &workspace_idents[name];
+        if text {
+            println!("\nUnused workspace dependency: {}", name);
+        }
+        unused_workspace_dependencies.push(UnusedDependency {
+            name: name.clone(),
+            manifest_path: workspace_manifest.display().to_string(),
+        });
+        if fix {
+            if let Item::Table(ref mut tbl) = workspace_doc["workspace"]["dependencies"] {
+                tbl.remove(&ident.manifest_key);
             }
         }
     }
 
-    Ok(())
+    if fix && !unused_workspace_dependencies.is_empty() {
+        fs::write(&workspace_manifest, workspace_doc.to_string())?;
+        if text {
+            println!("Updated {}", workspace_manifest.display());
+        }
+    }
+
+    let any_unused =
+        reports.iter().any(PackageReport::has_unused) || !unused_workspace_dependencies.is_empty();
+
+    if filter.format == OutputFormat::Json {
+        let report = Report { packages: reports, unused_workspace_dependencies };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    }
+
+    if !fix && any_unused {
+        Ok(std::process::ExitCode::FAILURE)
+    } else {
+        Ok(std::process::ExitCode::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn use_tree(src: &str) -> syn::UseTree {
+        syn::parse_str::<syn::ItemUse>(src).unwrap().tree
+    }
+
+    fn collect(src: &str) -> HashSet<String> {
+        let mut used = HashSet::new();
+        collect_use_tree(&use_tree(src), None, &mut used);
+        used
+    }
+
+    #[test]
+    fn collects_simple_use() {
+        assert_eq!(collect("use serde_json::Value;"), HashSet::from(["serde_json".to_string()]));
+    }
+
+    #[test]
+    fn collects_root_of_grouped_use() {
+        assert_eq!(
+            collect("use tokio::{fs, io::AsyncReadExt};"),
+            HashSet::from(["tokio".to_string()])
+        );
+    }
+
+    #[test]
+    fn follows_rename_back_to_the_real_crate() {
+        // `as` only renames the local binding; the crate actually used is
+        // the one on the left of `as`, not `alias`.
+        assert_eq!(collect("use toml_edit as alias;"), HashSet::from(["toml_edit".to_string()]));
+    }
+
+    #[test]
+    fn collects_root_of_a_glob_import() {
+        assert_eq!(collect("use anyhow::*;"), HashSet::from(["anyhow".to_string()]));
+    }
+
+    #[test]
+    fn skips_reserved_roots() {
+        assert_eq!(collect("use self::helpers::thing;"), HashSet::new());
+        assert_eq!(collect("use crate::helpers::thing;"), HashSet::new());
+    }
+
+    fn deps_table(toml: &str) -> Document {
+        toml.parse().unwrap()
+    }
+
+    #[test]
+    fn dependency_idents_maps_plain_deps_to_their_own_ident() {
+        let doc = deps_table("[dependencies]\nserde = \"1\"\n");
+        let idents = dependency_idents(doc.get("dependencies"));
+        assert_eq!(idents["serde"].manifest_key, "serde");
+        assert_eq!(idents["serde"].ident, "serde");
+        assert!(!idents["serde"].workspace);
+    }
+
+    #[test]
+    fn dependency_idents_is_empty_for_a_missing_table() {
+        let doc = deps_table("[package]\nname = \"example\"\n");
+        assert!(dependency_idents(doc.get("dev-dependencies")).is_empty());
+    }
+
+    #[test]
+    fn dependency_idents_honors_package_rename() {
+        let doc = deps_table("[dependencies]\nmy_serde = { package = \"serde\", version = \"1\" }\n");
+        let idents = dependency_idents(doc.get("dependencies"));
+        let ident = &idents["serde"];
+        assert_eq!(ident.manifest_key, "my_serde");
+        assert_eq!(ident.ident, "my_serde");
+    }
+
+    #[test]
+    fn dependency_idents_marks_workspace_inherited_deps() {
+        let doc = deps_table("[dependencies]\nanyhow = { workspace = true }\n");
+        let idents = dependency_idents(doc.get("dependencies"));
+        assert!(idents["anyhow"].workspace);
+    }
+
+    #[test]
+    fn read_ignored_deps_is_empty_for_a_missing_metadata_table() {
+        let doc = deps_table("[package]\nname = \"example\"\n");
+        assert!(read_ignored_deps(doc.get("package").and_then(|pkg| pkg.get("metadata"))).is_empty());
+    }
+
+    #[test]
+    fn read_ignored_deps_is_empty_for_a_missing_ignored_key() {
+        let doc = deps_table("[package.metadata.cargo-dead]\n");
+        assert!(read_ignored_deps(doc.get("package").and_then(|pkg| pkg.get("metadata"))).is_empty());
+    }
+
+    #[test]
+    fn read_ignored_deps_is_empty_when_ignored_is_not_an_array() {
+        let doc = deps_table("[package.metadata.cargo-dead]\nignored = \"serde\"\n");
+        assert!(read_ignored_deps(doc.get("package").and_then(|pkg| pkg.get("metadata"))).is_empty());
+    }
+
+    #[test]
+    fn read_ignored_deps_skips_non_string_entries_and_normalizes_the_rest() {
+        let doc = deps_table("[package.metadata.cargo-dead]\nignored = [\"serde-json\", 1, true]\n");
+        let ignored = read_ignored_deps(doc.get("package").and_then(|pkg| pkg.get("metadata")));
+        assert_eq!(ignored, HashSet::from(["serde_json".to_string()]));
+    }
+
+    #[test]
+    fn scan_rust_file_only_sees_its_own_file_not_its_siblings() {
+        // Regression test: `build.rs` sits at the package root, the same
+        // directory that also holds `src/`. A directory-walking scan of
+        // that root would wrongly pick up `foo` from `src/lib.rs` even
+        // though `build.rs` never references it.
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("build.rs"), "fn main() {}\n").unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src").join("lib.rs"), "use foo::Bar;\n").unwrap();
+
+        let build_only = scan_rust_file(&dir.path().join("build.rs")).unwrap().into_combined();
+        assert!(!build_only.contains("foo"));
+
+        // Sanity check that the fixture would indeed have leaked `foo` had
+        // the build script still been scanned with a directory walk.
+        let whole_root = scan_rust_files(dir.path()).unwrap().into_combined();
+        assert!(whole_root.contains("foo"));
+    }
+
+    #[test]
+    fn resolve_ident_falls_back_when_dependency_is_missing() {
+        let idents = HashMap::new();
+        let (manifest_key, ident, is_workspace) = resolve_ident(&idents, "some-crate");
+        assert_eq!(manifest_key, "some-crate");
+        assert_eq!(ident, "some_crate");
+        assert!(!is_workspace);
+    }
+
+    fn workspace_idents(names: &[&str]) -> HashMap<String, DepIdent> {
+        names
+            .iter()
+            .map(|name| {
+                (
+                    name.to_string(),
+                    DepIdent {
+                        manifest_key: name.to_string(),
+                        ident: name.to_string(),
+                        workspace: true,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fold_workspace_usage_marks_used_when_any_member_uses_it() {
+        let idents = workspace_idents(&["serde"]);
+        let records = vec![
+            ("serde".to_string(), false, Scope::Normal),
+            ("serde".to_string(), true, Scope::Normal),
+        ];
+        let (used, eligible) = fold_workspace_usage(&idents, &records, true, true, true);
+        assert!(used["serde"]);
+        assert!(eligible["serde"]);
+    }
+
+    #[test]
+    fn fold_workspace_usage_ignores_records_from_unchecked_scopes() {
+        // A `--only-dev` run records normal-scope workspace dependencies
+        // without checking them (always `false`), so a normal-only workspace
+        // dependency must not look confirmed-unused.
+        let idents = workspace_idents(&["anyhow"]);
+        let records = vec![("anyhow".to_string(), false, Scope::Normal)];
+        let (used, eligible) = fold_workspace_usage(&idents, &records, false, true, false);
+        assert!(!used["anyhow"]);
+        assert!(!eligible["anyhow"]);
+    }
 }